@@ -1,26 +1,663 @@
+use alloc::vec::Vec;
 use vectormatrix::{Matrix, Vector};
 
 /// A simple dense layer for a neural network.
 pub struct DenseLayer<const IN: usize, const OUT: usize> {
     weights: Matrix<f32, OUT, IN>,
     biases: Vector<f32, OUT>,
+    /// Activation applied to the output, or `None` for a linear layer.
+    activation: Option<Activation>,
 }
 
 impl<const IN: usize, const OUT: usize> DenseLayer<IN, OUT> {
-    /// Creates a new dense layer with the given weights and biases.
-    pub fn new(weights_rows: [Vector<f32, IN>; OUT], biases: [f32; OUT]) -> Self {
+    /// Creates a new dense layer with the given weights, biases, and
+    /// activation (`None` for a linear layer, e.g. a regression head).
+    pub fn new(weights_rows: [Vector<f32, IN>; OUT], biases: [f32; OUT], activation: Option<Activation>) -> Self {
         Self {
             weights: Matrix::new_rows(weights_rows),
             biases: Vector::new(biases),
+            activation,
         }
     }
 
-    /// Performs the forward pass for this layer.
-    pub fn forward(&self, inputs: &Vector<f32, IN>) -> Vector<f32, OUT> {
+    /// Computes `W . inputs + b` without applying the activation.
+    fn forward_raw(&self, inputs: &Vector<f32, IN>) -> Vector<f32, OUT> {
         let output_matrix = self.weights * *inputs;
         let output_vector = output_matrix.columns()[0];
         output_vector + self.biases
     }
+
+    /// Performs the forward pass for this layer, including its activation.
+    pub fn forward(&self, inputs: &Vector<f32, IN>) -> Vector<f32, OUT> {
+        let mut output = self.forward_raw(inputs);
+        if let Some(activation) = self.activation {
+            activation.apply(&mut output);
+        }
+        output
+    }
+
+    /// Performs the forward pass with a single weight corrupted by `fault`,
+    /// modeling a stuck-at or bit-flip error in weight-memory transfer.
+    pub fn forward_faulty(&self, inputs: &Vector<f32, IN>, fault: &FaultConfig) -> Vector<f32, OUT> {
+        let mut rows = self.weights.rows();
+        let mut row = rows[fault.neuron];
+        let corrupted_bits = fault.kind.apply(row[fault.weight_index].to_bits(), fault.bit);
+        row[fault.weight_index] = f32::from_bits(corrupted_bits);
+        rows[fault.neuron] = row;
+
+        let faulty_weights = Matrix::new_rows(rows);
+        let output_matrix = faulty_weights * *inputs;
+        let mut output = output_matrix.columns()[0] + self.biases;
+        if let Some(activation) = self.activation {
+            activation.apply(&mut output);
+        }
+        output
+    }
+
+    /// Applies one gradient-descent update to this layer's weights and
+    /// biases: `W -= lr * grad_w`, `b -= lr * grad_b`.
+    fn apply_gradients(&mut self, grad_w: Matrix<f32, OUT, IN>, grad_b: Vector<f32, OUT>, lr: f32) {
+        self.weights = self.weights - grad_w * lr;
+        self.biases = self.biases - grad_b * lr;
+    }
+
+    /// Creates a layer with weights drawn from `init` (biases start at
+    /// zero), seeded explicitly so runs are reproducible in the kernel.
+    /// This scales to layers too large to hand-write weight arrays for.
+    pub fn with_initializer(init: Initializer, activation: Option<Activation>, seed: u64) -> Self {
+        let mut rng = XorShift64::new(seed);
+        let std_dev = match init {
+            Initializer::Zeros | Initializer::Constant(_) => 0.0,
+            Initializer::Xavier => (1.0 / IN as f32).sqrt(),
+            Initializer::Kaiming => (2.0 / IN as f32).sqrt(),
+        };
+
+        let sample = |init: Initializer, rng: &mut XorShift64| -> f32 {
+            match init {
+                Initializer::Zeros => 0.0,
+                Initializer::Constant(value) => value,
+                Initializer::Xavier | Initializer::Kaiming => rng.next_gaussian() * std_dev,
+            }
+        };
+
+        let weights_rows: [Vector<f32, IN>; OUT] =
+            core::array::from_fn(|_| Vector::new(core::array::from_fn(|_| sample(init, &mut rng))));
+
+        Self::new(weights_rows, [0.0; OUT], activation)
+    }
+}
+
+/// Strategy for initializing a [`DenseLayer`]'s weights via
+/// [`DenseLayer::with_initializer`]: Xavier uses stddev `sqrt(1/IN)`,
+/// Kaiming (for ReLU layers) uses `sqrt(2/IN)`.
+#[derive(Clone, Copy)]
+pub enum Initializer {
+    Zeros,
+    Constant(f32),
+    Xavier,
+    Kaiming,
+}
+
+/// An activation function applied to a [`DenseLayer`]'s output. The
+/// transcendental variants are evaluated via the polynomial [`exp`]
+/// approximation, since this kernel is `no_std` and has no `libm`.
+#[derive(Clone, Copy)]
+pub enum Activation {
+    Relu,
+    LeakyRelu(f32),
+    Sigmoid,
+    Tanh,
+    Softmax,
+}
+
+impl Activation {
+    /// Applies this activation to `vector` in place.
+    pub fn apply<const D: usize>(&self, vector: &mut Vector<f32, D>) {
+        match self {
+            Activation::Relu => relu(vector),
+            Activation::LeakyRelu(alpha) => {
+                for i in 0..D {
+                    if vector[i] < 0.0 {
+                        vector[i] *= alpha;
+                    }
+                }
+            }
+            Activation::Sigmoid => {
+                for i in 0..D {
+                    vector[i] = sigmoid(vector[i]);
+                }
+            }
+            Activation::Tanh => {
+                for i in 0..D {
+                    vector[i] = tanh(vector[i]);
+                }
+            }
+            Activation::Softmax => softmax(vector),
+        }
+    }
+
+    /// Pulls `upstream` (a gradient w.r.t. this activation's output `a`)
+    /// back through the activation in place, turning it into a gradient
+    /// w.r.t. the pre-activation input `z`. Elementwise activations just
+    /// scale by `f'(z[i])`; softmax applies the vector-Jacobian product
+    /// `a ⊙ (upstream - (upstream . a))` instead.
+    pub fn backward<const D: usize>(&self, z: &Vector<f32, D>, a: &Vector<f32, D>, upstream: &mut Vector<f32, D>) {
+        match self {
+            Activation::Relu => {
+                for i in 0..D {
+                    if z[i] <= 0.0 {
+                        upstream[i] = 0.0;
+                    }
+                }
+            }
+            Activation::LeakyRelu(alpha) => {
+                for i in 0..D {
+                    if z[i] < 0.0 {
+                        upstream[i] *= alpha;
+                    }
+                }
+            }
+            Activation::Sigmoid => {
+                for i in 0..D {
+                    upstream[i] *= a[i] * (1.0 - a[i]);
+                }
+            }
+            Activation::Tanh => {
+                for i in 0..D {
+                    upstream[i] *= 1.0 - a[i] * a[i];
+                }
+            }
+            Activation::Softmax => {
+                let mut dot = 0.0;
+                for i in 0..D {
+                    dot += upstream[i] * a[i];
+                }
+                for i in 0..D {
+                    upstream[i] = a[i] * (upstream[i] - dot);
+                }
+            }
+        }
+    }
+}
+
+/// Approximates `e^x` via range reduction (`x = k*ln2 + r`) and the degree-4
+/// Taylor series for `e^r` around 0, then rescales by `2^k` through direct
+/// manipulation of the `f32` exponent bits.
+pub fn exp(x: f32) -> f32 {
+    // Clamp to a sane range so the exponent bit trick below can't overflow.
+    let x = if x > 88.0 {
+        88.0
+    } else if x < -88.0 {
+        -88.0
+    } else {
+        x
+    };
+
+    let k = (x / core::f32::consts::LN_2).round();
+    let r = x - k * core::f32::consts::LN_2;
+
+    let r2 = r * r;
+    let poly = 1.0 + r + 0.5 * r2 + 0.166_666_67 * r2 * r + 0.041_666_67 * r2 * r2;
+
+    let scale = f32::from_bits(((k as i32 + 127) as u32) << 23);
+    poly * scale
+}
+
+/// `sigmoid(x) = 1 / (1 + e^-x)`.
+pub fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + exp(-x))
+}
+
+/// `tanh(x) = 2 * sigmoid(2x) - 1`.
+pub fn tanh(x: f32) -> f32 {
+    2.0 * sigmoid(2.0 * x) - 1.0
+}
+
+/// Numerically-stable softmax: subtracts the max element before
+/// exponentiating so large inputs don't overflow.
+pub fn softmax<const D: usize>(vector: &mut Vector<f32, D>) {
+    let mut max = vector[0];
+    for i in 1..D {
+        if vector[i] > max {
+            max = vector[i];
+        }
+    }
+
+    let mut sum = 0.0;
+    for i in 0..D {
+        vector[i] = exp(vector[i] - max);
+        sum += vector[i];
+    }
+
+    for i in 0..D {
+        vector[i] /= sum;
+    }
+}
+
+/// Describes a single injected hardware fault targeting one weight of a
+/// [`DenseLayer`].
+#[derive(Clone, Copy)]
+pub struct FaultConfig {
+    pub kind: FaultKind,
+    /// Index of the output neuron (weight row) affected.
+    pub neuron: usize,
+    /// Index of the weight within that neuron's row.
+    pub weight_index: usize,
+    /// Bit position (0-31) within the weight's `f32` bit pattern.
+    pub bit: u8,
+}
+
+/// The kind of single-bit fault to inject into a weight.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    StuckAtZero,
+    StuckAtOne,
+    BitFlip,
+}
+
+impl FaultKind {
+    /// Applies this fault to the raw bit pattern of an `f32`.
+    fn apply(self, bits: u32, bit: u8) -> u32 {
+        let mask = 1u32 << bit;
+        match self {
+            FaultKind::StuckAtZero => bits & !mask,
+            FaultKind::StuckAtOne => bits | mask,
+            FaultKind::BitFlip => bits ^ mask,
+        }
+    }
+}
+
+/// Runs a clean and a fault-injected forward pass over the same input and
+/// prints the per-element absolute difference over serial, so the
+/// safety-criticality of individual weights can be compared.
+pub fn report_fault_divergence<const IN: usize, const OUT: usize>(
+    layer: &DenseLayer<IN, OUT>,
+    inputs: &Vector<f32, IN>,
+    fault: &FaultConfig,
+) {
+    let clean = layer.forward(inputs);
+    let faulty = layer.forward_faulty(inputs, fault);
+
+    serial_println!("Fault divergence (|clean - faulty|):");
+    serial_print!("[ ");
+    for i in 0..OUT {
+        let diff = (clean[i] - faulty[i]).abs();
+        // Printed to 4 decimal places rather than truncated to i32: most
+        // mantissa-bit flips produce sub-1.0 deltas, and the whole point
+        // of this report is telling those apart from the noise floor.
+        serial_print!("{:.4} ", diff);
+    }
+    serial_println!("]");
+}
+
+/// A fully-connected network, trainable on-device via backpropagation.
+/// Fixed at exactly two layers (hidden, output) rather than an arbitrary
+/// stack; the hidden and output layers' activations are whatever was
+/// passed to [`DenseLayer::new`] when they were built, and [`train_step`]
+/// differentiates through them via [`Activation::backward`].
+///
+/// [`train_step`]: Self::train_step
+pub struct Network<const IN: usize, const HIDDEN: usize, const OUT: usize> {
+    pub hidden: DenseLayer<IN, HIDDEN>,
+    pub output: DenseLayer<HIDDEN, OUT>,
+}
+
+impl<const IN: usize, const HIDDEN: usize, const OUT: usize> Network<IN, HIDDEN, OUT> {
+    /// Builds a network from an already-constructed hidden and output layer.
+    pub fn new(hidden: DenseLayer<IN, HIDDEN>, output: DenseLayer<HIDDEN, OUT>) -> Self {
+        Self { hidden, output }
+    }
+
+    /// Runs a forward pass through both layers.
+    pub fn forward(&self, inputs: &Vector<f32, IN>) -> Vector<f32, OUT> {
+        self.output.forward(&self.hidden.forward(inputs))
+    }
+
+    /// Runs a forward pass, returning each layer's pre-activation `z` and
+    /// post-activation `a` — the values a backward pass needs to
+    /// differentiate through whatever [`Activation`] each layer was built
+    /// with.
+    fn forward_cached(
+        &self,
+        inputs: &Vector<f32, IN>,
+    ) -> (
+        Vector<f32, HIDDEN>,
+        Vector<f32, HIDDEN>,
+        Vector<f32, OUT>,
+        Vector<f32, OUT>,
+    ) {
+        let z_hidden = self.hidden.forward_raw(inputs);
+        let mut a_hidden = z_hidden;
+        if let Some(activation) = self.hidden.activation {
+            activation.apply(&mut a_hidden);
+        }
+
+        let z_out = self.output.forward_raw(&a_hidden);
+        let mut a_out = z_out;
+        if let Some(activation) = self.output.activation {
+            activation.apply(&mut a_out);
+        }
+
+        (z_hidden, a_hidden, z_out, a_out)
+    }
+
+    /// Runs one step of gradient descent on a single `(inputs, target)`
+    /// example using mean-squared error, returning the loss before the
+    /// update is applied.
+    pub fn train_step(&mut self, inputs: &Vector<f32, IN>, target: &Vector<f32, OUT>, lr: f32) -> f32 {
+        let (z_hidden, a_hidden, z_out, a_out) = self.forward_cached(inputs);
+
+        // Output-layer error for MSE: delta_out = a_out - target, then
+        // pulled back through the output activation's derivative.
+        let mut delta_out = a_out;
+        let mut loss = 0.0;
+        for i in 0..OUT {
+            delta_out[i] -= target[i];
+            loss += delta_out[i] * delta_out[i];
+        }
+        loss /= OUT as f32;
+        if let Some(activation) = self.output.activation {
+            activation.backward(&z_out, &a_out, &mut delta_out);
+        }
+
+        // Backprop through the hidden layer: delta_hidden = (W_out^T .
+        // delta_out), pulled back through the hidden activation's derivative.
+        let w_out_t = self.output.weights.transpose();
+        let mut delta_hidden = (w_out_t * delta_out).columns()[0];
+        if let Some(activation) = self.hidden.activation {
+            activation.backward(&z_hidden, &a_hidden, &mut delta_hidden);
+        }
+
+        // Weight gradients are the outer product delta . a^T, built
+        // row-by-row since `vectormatrix` has no outer-product helper.
+        let grad_w_out: [Vector<f32, HIDDEN>; OUT] = core::array::from_fn(|i| {
+            let mut row = a_hidden;
+            for j in 0..HIDDEN {
+                row[j] *= delta_out[i];
+            }
+            row
+        });
+        let grad_w_hidden: [Vector<f32, IN>; HIDDEN] = core::array::from_fn(|i| {
+            let mut row = *inputs;
+            for j in 0..IN {
+                row[j] *= delta_hidden[i];
+            }
+            row
+        });
+
+        self.output.apply_gradients(Matrix::new_rows(grad_w_out), delta_out, lr);
+        self.hidden.apply_gradients(Matrix::new_rows(grad_w_hidden), delta_hidden, lr);
+
+        loss
+    }
+
+    /// Trains over `dataset` for `epochs` passes, printing the average loss
+    /// for each epoch over serial.
+    pub fn train(&mut self, dataset: &[(Vector<f32, IN>, Vector<f32, OUT>)], epochs: usize, lr: f32) {
+        for epoch in 0..epochs {
+            let mut total_loss = 0.0;
+            for (inputs, target) in dataset {
+                total_loss += self.train_step(inputs, target, lr);
+            }
+            let avg_loss = total_loss / dataset.len() as f32;
+            serial_println!("epoch {}: loss = {}", epoch, avg_loss as i32);
+        }
+    }
+}
+
+/// A tiny xorshift64 PRNG, standing in for the `rand` crate in this
+/// `no_std` kernel. Not cryptographically secure.
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    /// Seeds the generator. A seed of `0` would get stuck at `0` forever, so
+    /// it's replaced with a fixed non-zero value.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 { 0xdead_beef_cafe_babe } else { seed },
+        }
+    }
+
+    /// Returns the next pseudo-random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Returns a pseudo-random `f32` uniformly distributed in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Approximates a standard-normal sample via the central-limit trick:
+    /// the sum of 12 uniforms in `[0,1)` has mean 6 and unit variance.
+    pub fn next_gaussian(&mut self) -> f32 {
+        let mut sum = 0.0;
+        for _ in 0..12 {
+            sum += self.next_f32();
+        }
+        sum - 6.0
+    }
+}
+
+impl<const IN: usize, const HIDDEN: usize, const OUT: usize> Network<IN, HIDDEN, OUT> {
+    /// Number of genes in the flattened genome used by [`evolve`](Self::evolve).
+    const GENOME_LEN: usize = HIDDEN * IN + HIDDEN + OUT * HIDDEN + OUT;
+
+    /// Flattens this network's weights and biases into a single genome, in
+    /// the layout [`evolve`](Self::evolve) works on. `evolve` itself always
+    /// starts from a random population and takes no initial genome.
+    pub fn to_genome(&self) -> Vec<f32> {
+        let mut genome = Vec::with_capacity(Self::GENOME_LEN);
+        for row in self.hidden.weights.rows().iter() {
+            for j in 0..IN {
+                genome.push(row[j]);
+            }
+        }
+        for i in 0..HIDDEN {
+            genome.push(self.hidden.biases[i]);
+        }
+        for row in self.output.weights.rows().iter() {
+            for j in 0..HIDDEN {
+                genome.push(row[j]);
+            }
+        }
+        for i in 0..OUT {
+            genome.push(self.output.biases[i]);
+        }
+        genome
+    }
+
+    /// Rebuilds a network from a genome produced by [`to_genome`](Self::to_genome).
+    fn from_genome(genome: &[f32], hidden_activation: Option<Activation>, output_activation: Option<Activation>) -> Self {
+        let mut idx = 0;
+        let mut next = || {
+            let v = genome[idx];
+            idx += 1;
+            v
+        };
+
+        let hidden_rows: [Vector<f32, IN>; HIDDEN] =
+            core::array::from_fn(|_| Vector::new(core::array::from_fn(|_| next())));
+        let hidden_biases: [f32; HIDDEN] = core::array::from_fn(|_| next());
+        let output_rows: [Vector<f32, HIDDEN>; OUT] =
+            core::array::from_fn(|_| Vector::new(core::array::from_fn(|_| next())));
+        let output_biases: [f32; OUT] = core::array::from_fn(|_| next());
+
+        Self {
+            hidden: DenseLayer::new(hidden_rows, hidden_biases, hidden_activation),
+            output: DenseLayer::new(output_rows, output_biases, output_activation),
+        }
+    }
+
+    /// Evolves a population of networks with a genetic algorithm (roulette
+    /// selection, uniform crossover, per-gene mutation, elitism), for cases
+    /// where gradients aren't available. Prints best and mean fitness per
+    /// generation over serial.
+    pub fn evolve(
+        population_size: usize,
+        generations: usize,
+        p_mut: f32,
+        seed: u64,
+        hidden_activation: Option<Activation>,
+        output_activation: Option<Activation>,
+        fitness_fn: impl Fn(&Network<IN, HIDDEN, OUT>) -> f32,
+    ) -> Self {
+        let mut rng = XorShift64::new(seed);
+
+        let mut population: Vec<Vec<f32>> = (0..population_size)
+            .map(|_| (0..Self::GENOME_LEN).map(|_| rng.next_f32() * 2.0 - 1.0).collect())
+            .collect();
+
+        let mut best_genome = population[0].clone();
+        let mut best_fitness = f32::MIN;
+
+        for generation in 0..generations {
+            let fitnesses: Vec<f32> = population
+                .iter()
+                .map(|genome| fitness_fn(&Self::from_genome(genome, hidden_activation, output_activation)))
+                .collect();
+
+            let mut gen_best = 0;
+            let mut gen_sum = 0.0;
+            for (i, &fitness) in fitnesses.iter().enumerate() {
+                gen_sum += fitness;
+                if fitness > fitnesses[gen_best] {
+                    gen_best = i;
+                }
+            }
+            let gen_mean = gen_sum / population_size as f32;
+            serial_println!(
+                "generation {}: best = {}, mean = {}",
+                generation,
+                fitnesses[gen_best] as i32,
+                gen_mean as i32
+            );
+
+            if fitnesses[gen_best] > best_fitness {
+                best_fitness = fitnesses[gen_best];
+                best_genome = population[gen_best].clone();
+            }
+
+            // Roulette-wheel selection needs non-negative weights; shift by
+            // the minimum fitness (plus an epsilon) so every individual
+            // keeps a non-zero chance of being picked.
+            let min_fitness = fitnesses.iter().cloned().fold(f32::MAX, f32::min);
+            let weights: Vec<f32> = fitnesses.iter().map(|f| f - min_fitness + 1e-6).collect();
+            let total: f32 = weights.iter().sum();
+
+            let select = |rng: &mut XorShift64| -> usize {
+                let pick = rng.next_f32() * total;
+                let mut acc = 0.0;
+                for (i, &w) in weights.iter().enumerate() {
+                    acc += w;
+                    if acc >= pick {
+                        return i;
+                    }
+                }
+                weights.len() - 1
+            };
+
+            let mut next_population = Vec::with_capacity(population_size);
+            next_population.push(best_genome.clone());
+
+            while next_population.len() < population_size {
+                let parent_a = &population[select(&mut rng)];
+                let parent_b = &population[select(&mut rng)];
+
+                let mut child: Vec<f32> = (0..Self::GENOME_LEN)
+                    .map(|i| if rng.next_f32() < 0.5 { parent_a[i] } else { parent_b[i] })
+                    .collect();
+
+                for gene in child.iter_mut() {
+                    if rng.next_f32() < p_mut {
+                        *gene += rng.next_gaussian() * 0.1;
+                    }
+                }
+
+                next_population.push(child);
+            }
+
+            population = next_population;
+        }
+
+        Self::from_genome(&best_genome, hidden_activation, output_activation)
+    }
+}
+
+/// A leaky-integrate-and-fire spiking layer. Unlike [`DenseLayer`], it
+/// carries a membrane potential across calls that leaks towards zero each
+/// step and fires once it crosses `threshold`.
+pub struct SpikingLayer<const IN: usize, const OUT: usize> {
+    weights: Matrix<f32, OUT, IN>,
+    biases: Vector<f32, OUT>,
+    /// Membrane potential of each neuron, carried between steps.
+    potentials: Vector<f32, OUT>,
+    /// Fraction of the membrane potential retained each step, in `(0, 1)`.
+    leak: f32,
+    /// Potential at which a neuron fires.
+    threshold: f32,
+    /// Number of steps a neuron stays silent after firing.
+    refractory_period: u32,
+    /// Remaining silent steps for each neuron.
+    refractory: [u32; OUT],
+}
+
+impl<const IN: usize, const OUT: usize> SpikingLayer<IN, OUT> {
+    /// Creates a new spiking layer. Membrane potentials start at zero.
+    pub fn new(
+        weights_rows: [Vector<f32, IN>; OUT],
+        biases: [f32; OUT],
+        leak: f32,
+        threshold: f32,
+        refractory_period: u32,
+    ) -> Self {
+        Self {
+            weights: Matrix::new_rows(weights_rows),
+            biases: Vector::new(biases),
+            potentials: Vector::new([0.0; OUT]),
+            leak,
+            threshold,
+            refractory_period,
+            refractory: [0; OUT],
+        }
+    }
+
+    /// Advances the layer by one time step, returning a spike vector where
+    /// `1.0` marks a neuron that fired this step and `0.0` one that didn't.
+    pub fn step(&mut self, inputs: &Vector<f32, IN>) -> Vector<f32, OUT> {
+        let z_matrix = self.weights * *inputs;
+        let z = z_matrix.columns()[0] + self.biases;
+
+        let mut spikes = Vector::new([0.0; OUT]);
+        for i in 0..OUT {
+            if self.refractory[i] > 0 {
+                self.refractory[i] -= 1;
+                self.potentials[i] = 0.0;
+                continue;
+            }
+
+            self.potentials[i] = self.potentials[i] * self.leak + z[i];
+
+            if self.potentials[i] >= self.threshold {
+                spikes[i] = 1.0;
+                // Soft reset: subtract the threshold instead of zeroing, so
+                // any potential above it carries over to the next step. With
+                // a non-zero refractory period the silent steps above clear
+                // the potential, so nothing carries over in that case.
+                self.potentials[i] -= self.threshold;
+                self.refractory[i] = self.refractory_period;
+            }
+        }
+
+        spikes
+    }
 }
 
 /// A simple ReLU activation function.
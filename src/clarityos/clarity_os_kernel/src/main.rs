@@ -1,8 +1,9 @@
 #![no_std]
 #![no_main]
 
+extern crate alloc;
+
 use core::panic::PanicInfo;
-use core::alloc::{GlobalAlloc, Layout};
 use bootloader::{BootInfo, entry_point};
 use vectormatrix::Vector;
 
@@ -13,24 +14,21 @@ mod memory;
 mod llm;
 
 #[global_allocator]
-static ALLOCATOR: DummyAllocator = DummyAllocator;
+static ALLOCATOR: memory::Locked<memory::FreeListAllocator> =
+    memory::Locked::new(memory::FreeListAllocator::new());
 
-pub struct DummyAllocator;
+entry_point!(kernel_main);
 
-unsafe impl GlobalAlloc for DummyAllocator {
-    unsafe fn alloc(&self, _layout: Layout) -> *mut u8 {
-        panic!("no allocator")
-    }
+fn kernel_main(boot_info: &'static BootInfo) -> ! {
+    serial_println!("Hello from ClarityOS Kernel!");
 
-    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
-        panic!("no allocator")
+    unsafe {
+        memory::init_heap(&ALLOCATOR, boot_info);
     }
-}
 
-entry_point!(kernel_main);
-
-fn kernel_main(_boot_info: &'static BootInfo) -> ! {
-    serial_println!("Hello from ClarityOS Kernel!");
+    // The heap backs `alloc::Vec`, which the genetic-algorithm population
+    // and genomes below rely on; the layer sizes here are still fixed
+    // `DenseLayer<IN, OUT>` const generics, not runtime-decided.
 
     // Define a simple 2-layer neural network.
     let layer1 = llm::DenseLayer::new(
@@ -40,25 +38,88 @@ fn kernel_main(_boot_info: &'static BootInfo) -> ! {
             Vector::new([0.5, 0.6]),
         ], // 3x2 weights
         [0.1, 0.2, 0.3], // 3-element bias vector
+        Some(llm::Activation::Relu),
     );
     let layer2 = llm::DenseLayer::new(
         [Vector::new([0.7, 0.8, 0.9])], // 1x3 weights
         [0.4],                          // 1-element bias vector
+        None,                           // linear output head
     );
 
     // Create some sample input.
     let inputs = Vector::new([1.0, 2.0]);
     llm::print_vector(&inputs, "Input");
 
-    // Perform the forward pass.
-    let mut output1 = layer1.forward(&inputs);
-    llm::print_vector(&output1, "Layer 1 Output (before ReLU)");
-    llm::relu(&mut output1);
-    llm::print_vector(&output1, "Layer 1 Output (after ReLU)");
+    // Perform the forward pass (ReLU is applied inside layer1.forward()).
+    let output1 = layer1.forward(&inputs);
+    llm::print_vector(&output1, "Layer 1 Output");
 
     let final_output = layer2.forward(&output1);
     llm::print_vector(&final_output, "Final Output");
 
+    // Drive a small spiking layer over a few time steps to show spike
+    // trains building up on the serial console.
+    let mut spiking_layer = llm::SpikingLayer::new(
+        [
+            Vector::new([0.1, 0.2]),
+            Vector::new([0.3, 0.4]),
+            Vector::new([0.5, 0.6]),
+        ],
+        [0.1, 0.2, 0.3],
+        0.9,  // leak
+        1.0,  // threshold
+        2,    // refractory period
+    );
+    for step in 0..5 {
+        let spikes = spiking_layer.step(&inputs);
+        llm::print_vector(&spikes, "Spikes");
+        serial_println!("(step {})", step);
+    }
+
+    // Inject a single bit-flip fault into layer1's first weight and see how
+    // far the output drifts from the clean forward pass.
+    let fault = llm::FaultConfig {
+        kind: llm::FaultKind::BitFlip,
+        neuron: 0,
+        weight_index: 0,
+        bit: 30,
+    };
+    llm::report_fault_divergence(&layer1, &inputs, &fault);
+
+    // Train a small network on a tiny dataset to show the loss converging.
+    let mut network = llm::Network::new(layer1, layer2);
+    let dataset = [
+        (Vector::new([1.0, 2.0]), Vector::new([1.0])),
+        (Vector::new([0.5, 0.5]), Vector::new([0.0])),
+    ];
+    network.train(&dataset, 5, 0.01);
+
+    // Evolve a fresh population towards the same target as above, without
+    // using gradients at all.
+    let evolved: llm::Network<2, 3, 1> = llm::Network::evolve(
+        20,   // population size
+        10,   // generations
+        0.05, // p_mut
+        42,   // PRNG seed
+        Some(llm::Activation::Relu),
+        None,
+        |net| {
+            let mut error = 0.0;
+            for (inputs, target) in dataset.iter() {
+                let output = net.forward(inputs);
+                error += (output[0] - target[0]).abs();
+            }
+            -error // fitness is higher for lower error
+        },
+    );
+    llm::print_vector(&evolved.forward(&inputs), "Evolved Output");
+
+    // Build a wider layer with a Kaiming-initialized random start instead of
+    // hand-specified weights, and show it still runs a forward pass fine.
+    let wide_layer: llm::DenseLayer<2, 8> =
+        llm::DenseLayer::with_initializer(llm::Initializer::Kaiming, Some(llm::Activation::Relu), 7);
+    llm::print_vector(&wide_layer.forward(&inputs), "Wide Layer Output (Kaiming init)");
+
     serial_println!("LLM test complete.");
 
     loop {}
@@ -0,0 +1,225 @@
+//! Physical memory handling: turns the bootloader's memory map into a
+//! kernel heap backed by a hand-rolled free-list allocator.
+
+use bootloader::bootinfo::{BootInfo, MemoryRegionType};
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem;
+use core::ptr;
+use spin::Mutex;
+
+/// Size of a physical page, in bytes, as reported by the memory map.
+const PAGE_SIZE: u64 = 4096;
+
+/// A node in the intrusive free list. Lives inline at the start of every
+/// free block, so freeing memory never needs an allocation of its own.
+struct ListNode {
+    size: usize,
+    next: Option<&'static mut ListNode>,
+}
+
+impl ListNode {
+    const fn new(size: usize) -> Self {
+        ListNode { size, next: None }
+    }
+
+    fn start_addr(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn end_addr(&self) -> usize {
+        self.start_addr() + self.size
+    }
+}
+
+/// A first-fit free-list allocator. `alloc` takes the first block big
+/// enough for the request, splitting off the remainder; `dealloc` pushes
+/// the block back onto the list, coalescing with adjacent free neighbors.
+pub struct FreeListAllocator {
+    head: ListNode,
+}
+
+impl FreeListAllocator {
+    /// Creates an empty allocator. Call [`init`](Self::init) before using it.
+    pub const fn new() -> Self {
+        FreeListAllocator { head: ListNode::new(0) }
+    }
+
+    /// Initializes the allocator with the given heap bounds.
+    ///
+    /// # Safety
+    /// The caller must guarantee that `[heap_start, heap_start + heap_size)`
+    /// is unused and valid for the lifetime of the allocator.
+    pub unsafe fn init(&mut self, heap_start: usize, heap_size: usize) {
+        self.add_free_region(heap_start, heap_size);
+    }
+
+    /// Adds a region to the free list, merging it with any free neighbor
+    /// that is physically adjacent to it.
+    unsafe fn add_free_region(&mut self, addr: usize, mut size: usize) {
+        assert_eq!(align_up(addr, mem::align_of::<ListNode>()), addr);
+        assert!(size >= mem::size_of::<ListNode>());
+
+        let mut start = addr;
+        let mut current = &mut self.head;
+        loop {
+            let merged = match current.next {
+                Some(ref next) if next.end_addr() == start => true,
+                Some(ref next) if start + size == next.start_addr() => true,
+                _ => false,
+            };
+
+            if merged {
+                let absorbed = current.next.take().unwrap();
+                if absorbed.end_addr() == start {
+                    start = absorbed.start_addr();
+                }
+                size += absorbed.size;
+                current.next = absorbed.next.take();
+                // Restart the scan since the merged region may now be
+                // adjacent to a different neighbor.
+                current = &mut self.head;
+            } else if current.next.is_some() {
+                current = current.next.as_mut().unwrap();
+            } else {
+                break;
+            }
+        }
+
+        let mut node = ListNode::new(size);
+        node.next = self.head.next.take();
+        let node_ptr = start as *mut ListNode;
+        node_ptr.write(node);
+        self.head.next = Some(&mut *node_ptr);
+    }
+
+    /// Finds a free region that can hold `size` bytes at `align`, removing
+    /// it from the list and returning it along with the aligned start
+    /// address to allocate at.
+    fn find_region(&mut self, size: usize, align: usize) -> Option<(&'static mut ListNode, usize)> {
+        let mut current = &mut self.head;
+        while current.next.is_some() {
+            let region = current.next.as_deref().unwrap();
+            if let Ok(alloc_start) = Self::alloc_from_region(region, size, align) {
+                let next = current.next.as_mut().unwrap().next.take();
+                let region = current.next.take().unwrap();
+                current.next = next;
+                return Some((region, alloc_start));
+            }
+            current = current.next.as_mut().unwrap();
+        }
+        None
+    }
+
+    /// Checks whether `region` can hold an allocation of `size` at `align`,
+    /// returning the aligned start address if so.
+    fn alloc_from_region(region: &ListNode, size: usize, align: usize) -> Result<usize, ()> {
+        let alloc_start = align_up(region.start_addr(), align);
+        let alloc_end = alloc_start.checked_add(size).ok_or(())?;
+
+        if alloc_end > region.end_addr() {
+            return Err(());
+        }
+
+        let excess_size = region.end_addr() - alloc_end;
+        if excess_size > 0 && excess_size < mem::size_of::<ListNode>() {
+            // The leftover fragment couldn't hold its own header, so it
+            // would be lost; reject and let the search try another region.
+            return Err(());
+        }
+
+        Ok(alloc_start)
+    }
+
+    /// Adjusts a [`Layout`] so its size and alignment can also fit a
+    /// [`ListNode`] once the memory is freed again.
+    fn size_align(layout: Layout) -> (usize, usize) {
+        let layout = layout
+            .align_to(mem::align_of::<ListNode>())
+            .expect("adjusting alignment failed")
+            .pad_to_align();
+        let size = layout.size().max(mem::size_of::<ListNode>());
+        (size, layout.align())
+    }
+}
+
+fn align_up(addr: usize, align: usize) -> usize {
+    (addr + align - 1) & !(align - 1)
+}
+
+/// Wraps an allocator in a spinlock so it can be used as the kernel's
+/// `#[global_allocator]`.
+pub struct Locked<A> {
+    inner: Mutex<A>,
+}
+
+impl<A> Locked<A> {
+    pub const fn new(inner: A) -> Self {
+        Locked { inner: Mutex::new(inner) }
+    }
+
+    pub fn lock(&self) -> spin::MutexGuard<A> {
+        self.inner.lock()
+    }
+}
+
+unsafe impl GlobalAlloc for Locked<FreeListAllocator> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (size, align) = FreeListAllocator::size_align(layout);
+        let mut allocator = self.lock();
+
+        if let Some((region, alloc_start)) = allocator.find_region(size, align) {
+            // Read the region's end before re-adding the leading fragment,
+            // which writes a new header over the region's own.
+            let region_end = region.end_addr();
+            let leading_size = alloc_start - region.start_addr();
+            if leading_size >= mem::size_of::<ListNode>() {
+                allocator.add_free_region(region.start_addr(), leading_size);
+            }
+
+            let alloc_end = alloc_start.checked_add(size).expect("overflow during allocation");
+            let excess_size = region_end - alloc_end;
+            if excess_size > 0 {
+                allocator.add_free_region(alloc_end, excess_size);
+            }
+            alloc_start as *mut u8
+        } else {
+            ptr::null_mut()
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let (size, _) = FreeListAllocator::size_align(layout);
+        self.lock().add_free_region(ptr as usize, size);
+    }
+}
+
+/// Scans the bootloader-provided memory map for the largest usable region
+/// and hands it to `allocator` as the kernel heap.
+///
+/// The memory map reports physical frame numbers; the bootloader (built
+/// with the `map_physical_memory` feature) maps all of physical memory at
+/// `boot_info.physical_memory_offset`, so that offset has to be added
+/// before the range is treated as a dereferenceable pointer.
+///
+/// # Safety
+/// Must be called exactly once, early in `kernel_main`, before any
+/// allocation (including anything from the `alloc` crate) is attempted.
+pub unsafe fn init_heap(allocator: &Locked<FreeListAllocator>, boot_info: &'static BootInfo) {
+    let region = boot_info
+        .memory_map
+        .iter()
+        .filter(|region| region.region_type == MemoryRegionType::Usable)
+        .max_by_key(|region| region.range.end_frame_number - region.range.start_frame_number)
+        .expect("no usable memory region in the bootloader memory map");
+
+    let phys_mem_offset = boot_info.physical_memory_offset;
+    let heap_start = (phys_mem_offset + region.range.start_frame_number * PAGE_SIZE) as usize;
+    let heap_size = ((region.range.end_frame_number - region.range.start_frame_number) * PAGE_SIZE) as usize;
+
+    allocator.lock().init(heap_start, heap_size);
+    serial_println!(
+        "Heap initialized: {} KiB at {:#x}",
+        heap_size / 1024,
+        heap_start
+    );
+}